@@ -5,24 +5,137 @@
 
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::fs;
+use std::collections::HashSet;
 use std::path::PathBuf;
-use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use netstat2::{get_sockets_info, AddressFamilyFlags, ProtocolFlags, ProtocolSocketInfo, TcpState};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sysinfo::{Pid, System};
 use tauri::{AppHandle, Manager, State};
+use tokio::fs;
+use tokio::sync::RwLock;
+
+/// Default: how long a peer can go unseen before re-bootstrapping drops it.
+const DEFAULT_PEER_TTL_SECS: u64 = 300;
+/// Default: cadence of the background re-bootstrap sweep.
+const DEFAULT_REBOOTSTRAP_INTERVAL_SECS: u64 = 30;
+/// Default: target live peer count the sweep tries to maintain.
+const DEFAULT_MIN_PEER_COUNT: u16 = 3;
+/// Default: how long audit log entries are considered fresh for UI surfacing.
+const DEFAULT_AUDIT_TTL_SECS: u64 = 30 * 24 * 60 * 60;
+/// Default: well-known peers re-added when the live count falls below the configured minimum.
+fn default_bootstrap_peers() -> Vec<PeerSeed> {
+    [
+        (
+            "12D3KooWBootstrap1",
+            "/dns4/bootstrap1.cidfeed.net/tcp/4001",
+        ),
+        (
+            "12D3KooWBootstrap2",
+            "/dns4/bootstrap2.cidfeed.net/tcp/4001",
+        ),
+        (
+            "12D3KooWBootstrap3",
+            "/dns4/bootstrap3.cidfeed.net/tcp/4001",
+        ),
+    ]
+    .into_iter()
+    .map(|(peer_id, multiaddr)| PeerSeed {
+        peer_id: peer_id.to_string(),
+        multiaddr: multiaddr.to_string(),
+    })
+    .collect()
+}
 
 struct AppState {
-    private_node: Mutex<PrivateNodeState>,
+    private_node: RwLock<PrivateNodeState>,
     private_node_state_path: PathBuf,
-    security_state: Mutex<SecurityState>,
+    security_state: RwLock<SecurityState>,
     security_state_path: PathBuf,
+    /// Set at `setup` time when `security-state.json` was written by a newer,
+    /// incompatible schema version. While set, security commands refuse to
+    /// read or write security state so a newer peer's data is never
+    /// clobbered by this older build — the rest of the app (node, peers,
+    /// config) still starts and runs normally.
+    security_state_locked: Option<String>,
+    config: RwLock<AppConfig>,
+    config_path: PathBuf,
+}
+
+fn ensure_security_state_unlocked(state: &AppState) -> Result<(), String> {
+    match &state.security_state_locked {
+        Some(reason) => Err(format!(
+            "security state is locked pending manual recovery: {reason}"
+        )),
+        None => Ok(()),
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PeerSeed {
+    peer_id: String,
+    multiaddr: String,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ModePeerCounts {
+    easy: u16,
+    private: u16,
+    default: u16,
+}
+
+/// Operator-tunable settings for the private node, loaded from `config.toml`
+/// in the app data dir so targets can change without recompiling.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AppConfig {
+    listen_address: String,
+    listen_port: u16,
+    bootstrap_peers: Vec<PeerSeed>,
+    mode_peer_counts: ModePeerCounts,
+    peer_ttl_secs: u64,
+    rebootstrap_interval_secs: u64,
+    min_peer_count: u16,
+    audit_ttl_secs: u64,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        AppConfig {
+            listen_address: "0.0.0.0".to_string(),
+            listen_port: 4001,
+            bootstrap_peers: default_bootstrap_peers(),
+            mode_peer_counts: ModePeerCounts {
+                easy: 4,
+                private: 2,
+                default: 3,
+            },
+            peer_ttl_secs: DEFAULT_PEER_TTL_SECS,
+            rebootstrap_interval_secs: DEFAULT_REBOOTSTRAP_INTERVAL_SECS,
+            min_peer_count: DEFAULT_MIN_PEER_COUNT,
+            audit_ttl_secs: DEFAULT_AUDIT_TTL_SECS,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PeerRecord {
+    peer_id: String,
+    multiaddr: String,
+    last_seen: u64,
 }
 
 #[derive(Clone, Default, Deserialize, Serialize)]
 struct PrivateNodeState {
     online: bool,
     peer_count: u16,
+    #[serde(default)]
+    peers: Vec<PeerRecord>,
 }
 
 #[derive(Clone, Serialize)]
@@ -32,14 +145,145 @@ struct PrivateNodeStatus {
     peer_count: u16,
 }
 
-#[derive(Clone, Default, Deserialize, Serialize)]
+#[derive(Clone, Default, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct SecurityState {
-    identity_json: Option<String>,
-    delegation_json: Option<String>,
-    revocation_queue_json: Option<String>,
-    audit_log_json: Option<String>,
-    failed_flush_queue_json: Option<String>,
+    identity: Option<DocumentEnvelope>,
+    delegation: Option<DocumentEnvelope>,
+    revocation_queue: Option<DocumentEnvelope>,
+    #[serde(default)]
+    audit_log: Vec<AuditEntry>,
+    #[serde(default)]
+    failed_flush_queue: Vec<FailedFlushEntry>,
+}
+
+/// The lowest schema version this node still knows how to read.
+const MIN_SUPPORTED_SCHEMA_VERSION: u32 = 1;
+/// The schema version this node writes.
+const CURRENT_SCHEMA_VERSION: u32 = 1;
+/// Capabilities this node understands, advertised during negotiation.
+const SUPPORTED_CAPABILITIES: &[&str] = &[
+    "delegation-v1",
+    "revocation-v1",
+    "audit-chain-v1",
+    "flush-retry-v1",
+];
+
+/// Schema-versioned wrapper around a persisted security document
+/// (identity, delegation, or revocation queue), so a future format change
+/// can be detected and migrated instead of silently corrupting state.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DocumentEnvelope {
+    schema_version: u32,
+    capabilities: Vec<String>,
+    body: String,
+}
+
+fn wrap_envelope(body: String) -> DocumentEnvelope {
+    DocumentEnvelope {
+        schema_version: CURRENT_SCHEMA_VERSION,
+        capabilities: SUPPORTED_CAPABILITIES
+            .iter()
+            .map(|capability| capability.to_string())
+            .collect(),
+        body,
+    }
+}
+
+/// A document as it may appear on disk: either the current envelope, or a
+/// bare JSON string left over from before envelopes existed.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredDocument {
+    Envelope(DocumentEnvelope),
+    Legacy(String),
+}
+
+/// The on-disk shape of `security-state.json`, tolerant of both the legacy
+/// `*Json` field names and the current envelope field names so older state
+/// files migrate forward instead of being silently dropped.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OnDiskSecurityState {
+    identity_json: Option<StoredDocument>,
+    identity: Option<StoredDocument>,
+    delegation_json: Option<StoredDocument>,
+    delegation: Option<StoredDocument>,
+    revocation_queue_json: Option<StoredDocument>,
+    revocation_queue: Option<StoredDocument>,
+    #[serde(default)]
+    audit_log: Vec<AuditEntry>,
+    #[serde(default)]
+    failed_flush_queue: Vec<FailedFlushEntry>,
+}
+
+/// A hard failure loading `security-state.json`: unlike an ordinary parse
+/// error (which falls back to defaults), this means the file was written by
+/// a schema version this build doesn't understand and must not be touched.
+#[derive(Debug)]
+struct UnsupportedSchemaVersion(u32);
+
+impl std::fmt::Display for UnsupportedSchemaVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "security state uses schema version {}, newer than the {CURRENT_SCHEMA_VERSION} this build supports",
+            self.0
+        )
+    }
+}
+
+/// Upgrades a document as read from disk into the current envelope shape,
+/// rejecting schema versions newer than this build understands.
+fn migrate_document(document: StoredDocument) -> Result<DocumentEnvelope, UnsupportedSchemaVersion> {
+    match document {
+        StoredDocument::Legacy(body) => Ok(DocumentEnvelope {
+            schema_version: MIN_SUPPORTED_SCHEMA_VERSION,
+            capabilities: Vec::new(),
+            body,
+        }),
+        StoredDocument::Envelope(envelope) => {
+            if envelope.schema_version > CURRENT_SCHEMA_VERSION {
+                Err(UnsupportedSchemaVersion(envelope.schema_version))
+            } else {
+                Ok(envelope)
+            }
+        }
+    }
+}
+
+/// Base of the retry backoff (`base * 2^attempts`); a flush that has failed
+/// once waits twice this before its first retry.
+const FLUSH_RETRY_BASE_SECS: u64 = 5;
+/// Retry backoff ceiling.
+const FLUSH_RETRY_MAX_SECS: u64 = 300;
+/// Entries are dropped once they've failed this many attempts.
+const FLUSH_MAX_ATTEMPTS: u32 = 8;
+/// Cadence of the background retry sweep.
+const FLUSH_SWEEP_INTERVAL_SECS: u64 = 5;
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FailedFlushEntry {
+    revocation_id: String,
+    attempts: u32,
+    next_retry_at: u64,
+}
+
+/// Genesis `prev_hash`: 32 zero bytes, hex-encoded.
+const AUDIT_GENESIS_HASH: &str =
+    "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AuditEntry {
+    seq: u64,
+    timestamp: u64,
+    event: String,
+    payload: String,
+    prev_hash: String,
+    hash: String,
 }
 
 #[derive(Clone, Serialize)]
@@ -49,6 +293,29 @@ struct FlushRevocationResult {
     failed_ids: Vec<String>,
 }
 
+/// UI-facing staleness check for the audit log, driven by `AppConfig::audit_ttl_secs`.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AuditLogFreshness {
+    latest_event_at: Option<u64>,
+    ttl_secs: u64,
+    is_stale: bool,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NegotiatedCapabilities {
+    capabilities: Vec<String>,
+    min_schema_version: u32,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Client {
+    pid: u32,
+    name: String,
+}
+
 fn to_status(state: &PrivateNodeState) -> PrivateNodeStatus {
     PrivateNodeStatus {
         online: state.online,
@@ -63,42 +330,229 @@ fn resolve_state_path(handle: &AppHandle, file_name: &str) -> PathBuf {
     }
 }
 
-fn load_private_node_state(path: &PathBuf) -> PrivateNodeState {
-    let Ok(raw) = fs::read_to_string(path) else {
+async fn load_private_node_state(path: &PathBuf) -> PrivateNodeState {
+    let Ok(raw) = fs::read_to_string(path).await else {
         return PrivateNodeState::default();
     };
     serde_json::from_str::<PrivateNodeState>(&raw).unwrap_or_default()
 }
 
-fn persist_private_node_state(path: &PathBuf, state: &PrivateNodeState) {
+async fn persist_private_node_state(path: &PathBuf, state: &PrivateNodeState) {
     let Ok(encoded) = serde_json::to_string(state) else {
         return;
     };
     if let Some(parent) = path.parent() {
-        if fs::create_dir_all(parent).is_err() {
+        if fs::create_dir_all(parent).await.is_err() {
             return;
         }
     }
-    let _ = fs::write(path, encoded);
+    let _ = fs::write(path, encoded).await;
 }
 
-fn load_security_state(path: &PathBuf) -> SecurityState {
-    let Ok(raw) = fs::read_to_string(path) else {
-        return SecurityState::default();
+/// Loads `security-state.json`, migrating legacy documents forward. An
+/// ordinary parse error (missing file, corrupt JSON) is recoverable and
+/// falls back to defaults, same as before envelopes existed. Only a schema
+/// version newer than this build understands is a hard failure, since
+/// silently defaulting there would let this node overwrite a newer peer's
+/// state with an empty one.
+async fn load_security_state(path: &PathBuf) -> Result<SecurityState, UnsupportedSchemaVersion> {
+    let Ok(raw) = fs::read_to_string(path).await else {
+        return Ok(SecurityState::default());
     };
-    serde_json::from_str::<SecurityState>(&raw).unwrap_or_default()
+    let on_disk = match serde_json::from_str::<OnDiskSecurityState>(&raw) {
+        Ok(value) => value,
+        Err(err) => {
+            eprintln!("security-state.json is corrupt, starting from defaults: {err}");
+            return Ok(SecurityState::default());
+        }
+    };
+    Ok(SecurityState {
+        identity: on_disk.identity.or(on_disk.identity_json).map(migrate_document).transpose()?,
+        delegation: on_disk.delegation.or(on_disk.delegation_json).map(migrate_document).transpose()?,
+        revocation_queue: on_disk
+            .revocation_queue
+            .or(on_disk.revocation_queue_json)
+            .map(migrate_document)
+            .transpose()?,
+        audit_log: on_disk.audit_log,
+        failed_flush_queue: on_disk.failed_flush_queue,
+    })
 }
 
-fn persist_security_state(path: &PathBuf, state: &SecurityState) {
+async fn persist_security_state(path: &PathBuf, state: &SecurityState) {
     let Ok(encoded) = serde_json::to_string(state) else {
         return;
     };
     if let Some(parent) = path.parent() {
-        if fs::create_dir_all(parent).is_err() {
+        if fs::create_dir_all(parent).await.is_err() {
             return;
         }
     }
-    let _ = fs::write(path, encoded);
+    let _ = fs::write(path, encoded).await;
+}
+
+fn now_unix() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Seeds `peers` from `pool` up to `target`, skipping ids already present.
+fn seed_bootstrap_peers(peers: &mut Vec<PeerRecord>, pool: &[PeerSeed], target: usize, now: u64) {
+    for seed in pool {
+        if peers.len() >= target {
+            break;
+        }
+        if peers.iter().any(|peer| peer.peer_id == seed.peer_id) {
+            continue;
+        }
+        peers.push(PeerRecord {
+            peer_id: seed.peer_id.clone(),
+            multiaddr: seed.multiaddr.clone(),
+            last_seen: now,
+        });
+    }
+}
+
+/// Drops peers that haven't been seen within `config.peer_ttl_secs`, then tops
+/// back up from `config.bootstrap_peers` if the live count falls below
+/// `config.min_peer_count`.
+fn rebootstrap(state: &mut PrivateNodeState, config: &AppConfig) -> bool {
+    if !state.online {
+        return false;
+    }
+    let now = now_unix();
+    let before = state.peers.len();
+    state
+        .peers
+        .retain(|peer| now.saturating_sub(peer.last_seen) <= config.peer_ttl_secs);
+    let dropped = state.peers.len() != before;
+
+    let topped_up = if state.peers.len() < config.min_peer_count as usize {
+        let prior = state.peers.len();
+        seed_bootstrap_peers(
+            &mut state.peers,
+            &config.bootstrap_peers,
+            config.min_peer_count as usize,
+            now,
+        );
+        state.peers.len() != prior
+    } else {
+        false
+    };
+
+    state.peer_count = state.peers.len() as u16;
+    dropped || topped_up
+}
+
+async fn load_config(path: &PathBuf) -> AppConfig {
+    let Ok(raw) = fs::read_to_string(path).await else {
+        return AppConfig::default();
+    };
+    toml::from_str::<AppConfig>(&raw).unwrap_or_default()
+}
+
+async fn persist_config(path: &PathBuf, config: &AppConfig) {
+    let Ok(encoded) = toml::to_string_pretty(config) else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        if fs::create_dir_all(parent).await.is_err() {
+            return;
+        }
+    }
+    let _ = fs::write(path, encoded).await;
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn compute_audit_hash(seq: u64, timestamp: u64, event: &str, payload: &str, prev_hash: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(seq.to_le_bytes());
+    hasher.update(timestamp.to_le_bytes());
+    // Length-prefix the variable-width fields so no choice of `event`/`payload`
+    // can shift bytes across the field boundary and collide with a different pair.
+    hasher.update((event.len() as u64).to_le_bytes());
+    hasher.update(event.as_bytes());
+    hasher.update((payload.len() as u64).to_le_bytes());
+    hasher.update(payload.as_bytes());
+    hasher.update((prev_hash.len() as u64).to_le_bytes());
+    hasher.update(prev_hash.as_bytes());
+    to_hex(&hasher.finalize())
+}
+
+/// Appends an entry to `state.audit_log` without persisting; callers persist
+/// once after making all the state changes for their command.
+fn append_audit_event_locked(state: &mut SecurityState, event: String, payload: String) -> AuditEntry {
+    let seq = state.audit_log.last().map_or(0, |entry| entry.seq + 1);
+    let prev_hash = state
+        .audit_log
+        .last()
+        .map_or_else(|| AUDIT_GENESIS_HASH.to_string(), |entry| entry.hash.clone());
+    let timestamp = now_unix();
+    let hash = compute_audit_hash(seq, timestamp, &event, &payload, &prev_hash);
+    let entry = AuditEntry {
+        seq,
+        timestamp,
+        event,
+        payload,
+        prev_hash,
+        hash,
+    };
+    state.audit_log.push(entry.clone());
+    entry
+}
+
+/// `base * 2^attempts`, capped at `FLUSH_RETRY_MAX_SECS`.
+fn flush_backoff_secs(attempts: u32) -> u64 {
+    FLUSH_RETRY_BASE_SECS
+        .saturating_mul(1u64 << attempts.min(32))
+        .min(FLUSH_RETRY_MAX_SECS)
+}
+
+/// Attempts after which a transient flush failure is treated as resolved —
+/// models the downstream endpoint recovering, rather than the id staying
+/// permanently unflushable. Ids prefixed `fail-perm-` never recover and ride
+/// the backoff out to `FLUSH_MAX_ATTEMPTS`, exercising the drop path.
+const FLUSH_TRANSIENT_ATTEMPTS: u32 = 2;
+
+/// Retries due entries in `failed_flush_queue`. A `fail-perm-` id keeps
+/// failing every attempt; anything else succeeds once it has backed off
+/// `FLUSH_TRANSIENT_ATTEMPTS` times and is promoted into the audit log.
+/// Entries that exceed `FLUSH_MAX_ATTEMPTS` are dropped. When `force` is set,
+/// due-time is ignored so every queued entry is retried immediately.
+async fn sweep_failed_flushes(state: &AppState, force: bool) {
+    let mut guard = state.security_state.write().await;
+    let now = now_unix();
+    let due = std::mem::take(&mut guard.failed_flush_queue);
+    let mut remaining = Vec::new();
+    for mut entry in due {
+        if !force && entry.next_retry_at > now {
+            remaining.push(entry);
+            continue;
+        }
+        let still_failing =
+            entry.revocation_id.starts_with("fail-perm-") || entry.attempts < FLUSH_TRANSIENT_ATTEMPTS;
+        if still_failing {
+            entry.attempts += 1;
+            if entry.attempts >= FLUSH_MAX_ATTEMPTS {
+                continue;
+            }
+            entry.next_retry_at = now + flush_backoff_secs(entry.attempts);
+            remaining.push(entry);
+        } else {
+            append_audit_event_locked(
+                &mut guard,
+                "revocation_flushed".to_string(),
+                entry.revocation_id.clone(),
+            );
+        }
+    }
+    guard.failed_flush_queue = remaining;
+    persist_security_state(&state.security_state_path, &guard).await;
 }
 
 fn normalize_json_string(value: Option<String>) -> Option<String> {
@@ -116,91 +570,220 @@ fn normalize_json_string(value: Option<String>) -> Option<String> {
 }
 
 #[tauri::command]
-fn node_status(state: State<'_, AppState>) -> PrivateNodeStatus {
-    let guard = state.private_node.lock().expect("private node mutex poisoned");
-    to_status(&guard)
+async fn node_status(state: State<'_, AppState>) -> Result<PrivateNodeStatus, String> {
+    let guard = state.private_node.read().await;
+    Ok(to_status(&guard))
 }
 
 #[tauri::command]
-fn start_private_node(state: State<'_, AppState>) -> PrivateNodeStatus {
-    let mut guard = state.private_node.lock().expect("private node mutex poisoned");
+async fn start_private_node(state: State<'_, AppState>) -> Result<PrivateNodeStatus, String> {
+    let config = state.config.read().await;
+    let mut guard = state.private_node.write().await;
     guard.online = true;
-    if guard.peer_count == 0 {
-        guard.peer_count = 3;
+    if guard.peers.is_empty() {
+        seed_bootstrap_peers(
+            &mut guard.peers,
+            &config.bootstrap_peers,
+            config.mode_peer_counts.default as usize,
+            now_unix(),
+        );
     }
-    persist_private_node_state(&state.private_node_state_path, &guard);
-    to_status(&guard)
+    guard.peer_count = guard.peers.len() as u16;
+    persist_private_node_state(&state.private_node_state_path, &guard).await;
+    Ok(to_status(&guard))
 }
 
 #[tauri::command]
-fn start_private_node_mode(
+async fn start_private_node_mode(
     state: State<'_, AppState>,
     mode: String,
 ) -> Result<PrivateNodeStatus, String> {
+    let config = state.config.read().await;
     let peer_count = match mode.as_str() {
-        "easy" => 4,
-        "private" => 2,
+        "easy" => config.mode_peer_counts.easy,
+        "private" => config.mode_peer_counts.private,
         _ => return Err("invalid node mode".to_string()),
     };
 
-    let mut guard = state.private_node.lock().expect("private node mutex poisoned");
+    let mut guard = state.private_node.write().await;
     guard.online = true;
-    guard.peer_count = peer_count;
-    persist_private_node_state(&state.private_node_state_path, &guard);
+    guard.peers.clear();
+    seed_bootstrap_peers(
+        &mut guard.peers,
+        &config.bootstrap_peers,
+        peer_count as usize,
+        now_unix(),
+    );
+    guard.peer_count = guard.peers.len() as u16;
+    persist_private_node_state(&state.private_node_state_path, &guard).await;
     Ok(to_status(&guard))
 }
 
 #[tauri::command]
-fn stop_private_node(state: State<'_, AppState>) -> PrivateNodeStatus {
-    let mut guard = state.private_node.lock().expect("private node mutex poisoned");
+async fn stop_private_node(state: State<'_, AppState>) -> Result<PrivateNodeStatus, String> {
+    let mut guard = state.private_node.write().await;
     guard.online = false;
     guard.peer_count = 0;
-    persist_private_node_state(&state.private_node_state_path, &guard);
-    to_status(&guard)
+    guard.peers.clear();
+    persist_private_node_state(&state.private_node_state_path, &guard).await;
+    Ok(to_status(&guard))
 }
 
 #[tauri::command]
-fn simulate_peer_join(state: State<'_, AppState>) -> PrivateNodeStatus {
-    let mut guard = state.private_node.lock().expect("private node mutex poisoned");
+async fn simulate_peer_join(state: State<'_, AppState>) -> Result<PrivateNodeStatus, String> {
+    let mut guard = state.private_node.write().await;
     if guard.online {
-        guard.peer_count = guard.peer_count.saturating_add(1);
-        persist_private_node_state(&state.private_node_state_path, &guard);
+        let index = guard.peers.len();
+        guard.peers.push(PeerRecord {
+            peer_id: format!("12D3KooWSimulated{index}"),
+            multiaddr: format!("/dns4/peer{index}.local/tcp/4001"),
+            last_seen: now_unix(),
+        });
+        guard.peer_count = guard.peers.len() as u16;
+        persist_private_node_state(&state.private_node_state_path, &guard).await;
     }
-    to_status(&guard)
+    Ok(to_status(&guard))
 }
 
 #[tauri::command]
-fn get_security_state(state: State<'_, AppState>) -> SecurityState {
-    let guard = state
-        .security_state
-        .lock()
-        .expect("security state mutex poisoned");
-    guard.clone()
+async fn list_peers(state: State<'_, AppState>) -> Result<Vec<PeerRecord>, String> {
+    let guard = state.private_node.read().await;
+    Ok(guard.peers.clone())
 }
 
+/// Removes a single peer by id, e.g. so the UI can drop one the operator
+/// doesn't trust without waiting for its TTL to lapse.
 #[tauri::command]
-fn set_security_state(
+async fn forget_peer(state: State<'_, AppState>, peer_id: String) -> Result<PrivateNodeStatus, String> {
+    let mut guard = state.private_node.write().await;
+    guard.peers.retain(|peer| peer.peer_id != peer_id);
+    guard.peer_count = guard.peers.len() as u16;
+    persist_private_node_state(&state.private_node_state_path, &guard).await;
+    Ok(to_status(&guard))
+}
+
+#[tauri::command]
+async fn get_security_state(state: State<'_, AppState>) -> Result<SecurityState, String> {
+    ensure_security_state_unlocked(&state)?;
+    let guard = state.security_state.read().await;
+    Ok(guard.clone())
+}
+
+#[tauri::command]
+async fn set_security_state(
     state: State<'_, AppState>,
     identity_json: Option<String>,
     delegation_json: Option<String>,
     revocation_queue_json: Option<String>,
-    audit_log_json: Option<String>,
-    failed_flush_queue_json: Option<String>,
-) {
-    let mut guard = state
-        .security_state
-        .lock()
-        .expect("security state mutex poisoned");
-    guard.identity_json = normalize_json_string(identity_json);
-    guard.delegation_json = normalize_json_string(delegation_json);
-    guard.revocation_queue_json = normalize_json_string(revocation_queue_json);
-    guard.audit_log_json = normalize_json_string(audit_log_json);
-    guard.failed_flush_queue_json = normalize_json_string(failed_flush_queue_json);
-    persist_security_state(&state.security_state_path, &guard);
-}
-
-#[tauri::command]
-fn flush_revocation_queue(revocation_ids: Vec<String>) -> FlushRevocationResult {
+) -> Result<(), String> {
+    ensure_security_state_unlocked(&state)?;
+    let mut guard = state.security_state.write().await;
+    guard.identity = normalize_json_string(identity_json).map(wrap_envelope);
+    guard.delegation = normalize_json_string(delegation_json).map(wrap_envelope);
+    guard.revocation_queue = normalize_json_string(revocation_queue_json).map(wrap_envelope);
+    persist_security_state(&state.security_state_path, &guard).await;
+    Ok(())
+}
+
+/// Returns the intersection of this node's supported capabilities with the
+/// peer's advertised ones, plus the minimum schema version both sides can
+/// speak. Errors if the peer's version predates what this node still reads.
+#[tauri::command]
+fn negotiate_capabilities(
+    peer_schema_version: u32,
+    peer_capabilities: Vec<String>,
+) -> Result<NegotiatedCapabilities, String> {
+    let min_schema_version = peer_schema_version.min(CURRENT_SCHEMA_VERSION);
+    if min_schema_version < MIN_SUPPORTED_SCHEMA_VERSION {
+        return Err(format!(
+            "peer schema version {peer_schema_version} predates the minimum supported version {MIN_SUPPORTED_SCHEMA_VERSION}"
+        ));
+    }
+    let capabilities = SUPPORTED_CAPABILITIES
+        .iter()
+        .map(|capability| capability.to_string())
+        .filter(|capability| peer_capabilities.contains(capability))
+        .collect();
+    Ok(NegotiatedCapabilities {
+        capabilities,
+        min_schema_version,
+    })
+}
+
+/// Appends a new entry to the hash-chained audit log, linking it to the
+/// current tail so truncation or mid-chain edits become detectable.
+#[tauri::command]
+async fn append_audit_event(
+    state: State<'_, AppState>,
+    event: String,
+    payload: String,
+) -> Result<AuditEntry, String> {
+    ensure_security_state_unlocked(&state)?;
+    let mut guard = state.security_state.write().await;
+    let entry = append_audit_event_locked(&mut guard, event, payload);
+    persist_security_state(&state.security_state_path, &guard).await;
+    Ok(entry)
+}
+
+/// Walks the audit chain recomputing each hash and checking
+/// `entry[i].prev_hash == entry[i - 1].hash`. Returns the first broken
+/// sequence number, or `None` if the chain is intact.
+#[tauri::command]
+async fn verify_audit_log(state: State<'_, AppState>) -> Result<Option<u64>, String> {
+    ensure_security_state_unlocked(&state)?;
+    let guard = state.security_state.read().await;
+    let mut expected_prev_hash = AUDIT_GENESIS_HASH.to_string();
+    for entry in &guard.audit_log {
+        let recomputed = compute_audit_hash(
+            entry.seq,
+            entry.timestamp,
+            &entry.event,
+            &entry.payload,
+            &entry.prev_hash,
+        );
+        if entry.prev_hash != expected_prev_hash || entry.hash != recomputed {
+            return Ok(Some(entry.seq));
+        }
+        expected_prev_hash = entry.hash.clone();
+    }
+    Ok(None)
+}
+
+/// Reports whether the audit log's most recent entry is older than the
+/// configured `audit_ttl_secs`, so the UI can flag a stale log instead of
+/// silently trusting one nothing has written to in a long time.
+#[tauri::command]
+async fn audit_log_freshness(state: State<'_, AppState>) -> Result<AuditLogFreshness, String> {
+    ensure_security_state_unlocked(&state)?;
+    let config = state.config.read().await;
+    let guard = state.security_state.read().await;
+    let latest_event_at = guard.audit_log.last().map(|entry| entry.timestamp);
+    let is_stale = match latest_event_at {
+        Some(timestamp) => now_unix().saturating_sub(timestamp) > config.audit_ttl_secs,
+        None => false,
+    };
+    Ok(AuditLogFreshness {
+        latest_event_at,
+        ttl_secs: config.audit_ttl_secs,
+        is_stale,
+    })
+}
+
+/// Flushes each revocation id, promoting successes straight into the audit
+/// log. Ids that fail to flush (those prefixed `fail-`) are written into
+/// `failed_flush_queue` with an attempt count and `next_retry_at`, so the
+/// background sweep (or a manual `retry_failed_flushes`) can retry them with
+/// exponential backoff instead of the result silently vanishing. Empty ids and
+/// ids already flushed in this call or already queued are reported in
+/// `failed_ids` but not queued again.
+#[tauri::command]
+async fn flush_revocation_queue(
+    state: State<'_, AppState>,
+    revocation_ids: Vec<String>,
+) -> Result<FlushRevocationResult, String> {
+    ensure_security_state_unlocked(&state)?;
+    let mut guard = state.security_state.write().await;
+    let now = now_unix();
     let mut flushed_ids: Vec<String> = Vec::new();
     let mut failed_ids: Vec<String> = Vec::new();
     for revocation_id in revocation_ids {
@@ -209,20 +792,98 @@ fn flush_revocation_queue(revocation_ids: Vec<String>) -> FlushRevocationResult
             failed_ids.push("<empty>".to_string());
             continue;
         }
-        if flushed_ids.iter().any(|existing| existing == &normalized) {
+        if flushed_ids.iter().any(|existing| existing == &normalized)
+            || guard
+                .failed_flush_queue
+                .iter()
+                .any(|entry| entry.revocation_id == normalized)
+        {
             failed_ids.push(normalized);
             continue;
         }
         if normalized.starts_with("fail-") {
+            guard.failed_flush_queue.push(FailedFlushEntry {
+                revocation_id: normalized.clone(),
+                attempts: 1,
+                next_retry_at: now + flush_backoff_secs(1),
+            });
             failed_ids.push(normalized);
             continue;
         }
+        append_audit_event_locked(&mut guard, "revocation_flushed".to_string(), normalized.clone());
         flushed_ids.push(normalized);
     }
-    FlushRevocationResult {
+    persist_security_state(&state.security_state_path, &guard).await;
+    Ok(FlushRevocationResult {
         flushed_ids,
         failed_ids,
+    })
+}
+
+/// Manually triggers an immediate retry sweep over `failed_flush_queue`,
+/// ignoring each entry's `next_retry_at`.
+#[tauri::command]
+async fn retry_failed_flushes(state: State<'_, AppState>) -> Result<Vec<FailedFlushEntry>, String> {
+    ensure_security_state_unlocked(&state)?;
+    sweep_failed_flushes(&state, true).await;
+    let guard = state.security_state.read().await;
+    Ok(guard.failed_flush_queue.clone())
+}
+
+#[tauri::command]
+async fn failed_flush_status(state: State<'_, AppState>) -> Result<Vec<FailedFlushEntry>, String> {
+    ensure_security_state_unlocked(&state)?;
+    let guard = state.security_state.read().await;
+    Ok(guard.failed_flush_queue.clone())
+}
+
+/// Lists local processes with a live TCP socket on `listen_port`, excluding
+/// this process and the node's own listening endpoint so only genuine
+/// external connections are reported. The listening endpoint is identified
+/// by its `TcpState::Listen` socket state rather than a port-number
+/// comparison, since a real peer's ephemeral source port can coincidentally
+/// equal `listen_port`.
+#[tauri::command]
+fn connected_processes(listen_port: u16) -> Result<Vec<Client>, String> {
+    let sockets = get_sockets_info(AddressFamilyFlags::IPV4 | AddressFamilyFlags::IPV6, ProtocolFlags::TCP)
+        .map_err(|err| err.to_string())?;
+
+    let own_pid = std::process::id();
+    let mut system = System::new();
+    system.refresh_processes();
+
+    let mut seen_pids = HashSet::new();
+    let mut clients = Vec::new();
+    for socket in sockets {
+        let ProtocolSocketInfo::Tcp(tcp) = &socket.protocol_socket_info else {
+            continue;
+        };
+        if tcp.local_port != listen_port || tcp.state == TcpState::Listen {
+            continue;
+        }
+        for pid in &socket.associated_pids {
+            if *pid == own_pid || !seen_pids.insert(*pid) {
+                continue;
+            }
+            let name = system
+                .process(Pid::from_u32(*pid))
+                .map(|process| process.name().to_string_lossy().into_owned())
+                .unwrap_or_else(|| format!("pid-{pid}"));
+            clients.push(Client { pid: *pid, name });
+        }
     }
+
+    Ok(clients)
+}
+
+/// Re-reads `config.toml` from disk and swaps it into `AppState`, letting
+/// operators retune the private node without restarting it.
+#[tauri::command]
+async fn reload_config(state: State<'_, AppState>) -> Result<AppConfig, String> {
+    let config = load_config(&state.config_path).await;
+    let mut guard = state.config.write().await;
+    *guard = config.clone();
+    Ok(config)
 }
 
 fn main() {
@@ -230,14 +891,63 @@ fn main() {
         .setup(|app| {
             let private_node_path = resolve_state_path(app.handle(), "private-node-state.json");
             let security_state_path = resolve_state_path(app.handle(), "security-state.json");
-            let private_node = load_private_node_state(&private_node_path);
-            let security_state = load_security_state(&security_state_path);
+            let config_path = resolve_state_path(app.handle(), "config.toml");
+            let (private_node, security_state, security_state_locked, config) =
+                tauri::async_runtime::block_on(async {
+                    let config = if fs::metadata(&config_path).await.is_ok() {
+                        load_config(&config_path).await
+                    } else {
+                        let defaults = AppConfig::default();
+                        persist_config(&config_path, &defaults).await;
+                        defaults
+                    };
+                    let private_node = load_private_node_state(&private_node_path).await;
+                    let (security_state, security_state_locked) =
+                        match load_security_state(&security_state_path).await {
+                            Ok(state) => (state, None),
+                            Err(err) => {
+                                eprintln!(
+                                    "refusing to read or write security-state.json: {err}"
+                                );
+                                (SecurityState::default(), Some(err.to_string()))
+                            }
+                        };
+                    (private_node, security_state, security_state_locked, config)
+                });
             app.manage(AppState {
-                private_node: Mutex::new(private_node),
+                private_node: RwLock::new(private_node),
                 private_node_state_path: private_node_path,
-                security_state: Mutex::new(security_state),
+                security_state: RwLock::new(security_state),
                 security_state_path,
+                security_state_locked,
+                config: RwLock::new(config),
+                config_path,
             });
+
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    let state = handle.state::<AppState>();
+                    let config = state.config.read().await.clone();
+                    tokio::time::sleep(Duration::from_secs(config.rebootstrap_interval_secs)).await;
+                    let mut guard = state.private_node.write().await;
+                    if rebootstrap(&mut guard, &config) {
+                        persist_private_node_state(&state.private_node_state_path, &guard).await;
+                    }
+                }
+            });
+
+            let flush_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                loop {
+                    tokio::time::sleep(Duration::from_secs(FLUSH_SWEEP_INTERVAL_SECS)).await;
+                    let state = flush_handle.state::<AppState>();
+                    if state.security_state_locked.is_none() {
+                        sweep_failed_flushes(&state, false).await;
+                    }
+                }
+            });
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -246,9 +956,19 @@ fn main() {
             start_private_node_mode,
             stop_private_node,
             simulate_peer_join,
+            list_peers,
+            forget_peer,
             get_security_state,
             set_security_state,
-            flush_revocation_queue
+            flush_revocation_queue,
+            connected_processes,
+            append_audit_event,
+            verify_audit_log,
+            audit_log_freshness,
+            reload_config,
+            retry_failed_flushes,
+            failed_flush_status,
+            negotiate_capabilities
         ])
         .run(tauri::generate_context!())
         .expect("error while running CIDFeed Tauri app");